@@ -1,14 +1,23 @@
+use std::{collections::VecDeque, ops::RangeInclusive};
+
 use bevy::{
-    pbr::{AmbientLight, PointLight, PointLightBundle},
+    input::Input,
+    pbr::{AlphaMode, AmbientLight, PointLight, PointLightBundle},
     prelude::{
         shape, App, Assets, BuildChildren, Camera3dBundle, ClearColor, Color, Commands, Component,
-        Entity, Mesh, Msaa, PbrBundle, Plugin, Query, Res, ResMut, Resource, SpatialBundle,
-        StandardMaterial, SystemSet, Transform, Vec2, Vec3, Visibility,
+        CursorMoved, Entity, EventReader, Handle, KeyCode, Mesh, Msaa, MouseButton, PbrBundle,
+        Plugin, Query, Res, ResMut, Resource, SpatialBundle, StandardMaterial, SystemSet,
+        Transform, Vec2, Vec3, Visibility,
     },
     time::FixedTimestep,
-    utils::HashMap,
+    utils::{HashMap, HashSet},
     DefaultPlugins,
 };
+use bevy_egui::{egui, EguiContext, EguiPlugin};
+use bevy_mod_raycast::{
+    DefaultRaycastingPlugin, RayCastMesh, RayCastMethod, RayCastSource, RaycastSystem,
+};
+use serde::{Deserialize, Serialize};
 use smooth_bevy_cameras::{
     controllers::orbit::{OrbitCameraBundle, OrbitCameraController, OrbitCameraPlugin},
     LookTransformPlugin,
@@ -19,26 +28,100 @@ const GRID_WIDTH: i32 = 20;
 const GRID_HEIGHT: i32 = 20;
 const GRID_DEPTH: i32 = 20;
 
+// Ctrl+S/Ctrl+L save and load the whole grid; Ctrl+E/Ctrl+I export and
+// import a standalone seed pattern translated by IMPORT_OFFSET. Both use
+// the same RON-backed Pattern format, see `save_load_input_system` and
+// `pattern_io_input_system`.
+const SAVE_FILE: &str = "grid.ron";
+const PATTERN_FILE: &str = "pattern.ron";
+const IMPORT_OFFSET: Position = Position { x: 0, y: 0, z: 0 };
+
+// Time-tower mode: when `GameOfLife::history_depth` is non-zero, each
+// generation is kept around instead of being overwritten in place and is
+// rendered as a slice stacked `STEP_HEIGHT` above the previous one, so the
+// whole evolution becomes a single "through time" sculpture.
+const HISTORY_DEPTH: usize = 0;
+const STEP_HEIGHT: f32 = 1.2;
+
+// How a live cube is colored each tick; see `ColorMode` and
+// `color_cells_system`. AGE_COLOR_SCALE is the number of consecutive
+// generations a cell needs to survive to reach full saturation in `Age`
+// mode.
+const COLOR_MODE: ColorMode = ColorMode::Age;
+const AGE_COLOR_SCALE: f32 = 20.0;
+
 // Candidates for the Game of Life in Three Dimensions, Carter Bays
 // Department of Computer Science, University of South Carolina, Columbia, SC 29208, USA
 // URL: https://content.wolfram.com/uploads/sites/13/2018/02/01-3-1.pdf
+// These are the default thresholds; `Rules` below makes them editable at
+// runtime instead of fixed at compile time.
 const EB: i32 = 4;
 const FB: i32 = 5;
 const EH: i32 = 5;
 const FH: i32 = 5;
 
-#[derive(Debug, Eq, PartialEq)]
-enum CellState {
-    Alive,
-    Dead,
+/// How a live cube's material is colored each tick: by how long it has
+/// survived, by its current live-neighbor count, or not at all (the
+/// original flat green).
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ColorMode {
+    Age,
+    Neighbors,
+    Static,
+}
+
+#[derive(Resource, Clone, Copy)]
+struct ColorConfig {
+    mode: ColorMode,
+}
+
+/// Survival/birth thresholds for the step, expressed as inclusive ranges of
+/// live-neighbor counts. A live cell survives when its neighbor count falls
+/// in `survival`; a dead cell is born when it falls in `birth`.
+#[derive(Resource, Clone)]
+struct Rules {
+    survival: RangeInclusive<i32>,
+    birth: RangeInclusive<i32>,
 }
 
-#[derive(Component, Debug)]
-struct Cell {
-    state: CellState,
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            survival: EB..=EH,
+            birth: FB..=FH,
+        }
+    }
 }
 
-#[derive(Component, Debug, Eq, PartialEq, Hash, Clone)]
+/// A named entry from Bays' catalogue of 3D Game of Life rules.
+#[derive(Clone)]
+struct RulePreset {
+    name: &'static str,
+    survival: RangeInclusive<i32>,
+    birth: RangeInclusive<i32>,
+}
+
+fn rule_presets() -> Vec<RulePreset> {
+    vec![
+        RulePreset {
+            name: "4555 (default)",
+            survival: 4..=5,
+            birth: 5..=5,
+        },
+        RulePreset {
+            name: "5766",
+            survival: 5..=7,
+            birth: 6..=6,
+        },
+        RulePreset {
+            name: "4566",
+            survival: 4..=5,
+            birth: 6..=6,
+        },
+    ]
+}
+
+#[derive(Component, Debug, Eq, PartialEq, Hash, Clone, Serialize, Deserialize)]
 struct Position {
     x: i32,
     y: i32,
@@ -51,66 +134,150 @@ impl Position {
     }
 }
 
-impl Cell {
-    fn new(state: CellState) -> Cell {
-        Cell { state }
-    }
+/// A live-cell list: the whole grid for save/load, or a standalone seed
+/// pattern for import/export. Serialized as RON so files stay hand-editable.
+#[derive(Serialize, Deserialize)]
+struct Pattern {
+    cells: Vec<Position>,
+}
 
-    fn is_alive(&self) -> bool {
-        self.state == CellState::Alive
-    }
+pub struct GameOfLife {
+    /// Number of past generations to keep stacked in time-tower mode. `0`
+    /// disables the mode and reproduces the original single-slice behavior.
+    history_depth: usize,
+    /// Distance, along the Y axis, between two consecutive generation
+    /// layers when time-tower mode is enabled.
+    step_height: f32,
+    /// How live cubes are colored each tick.
+    color_mode: ColorMode,
 }
 
-pub struct GameOfLife;
+impl Default for GameOfLife {
+    fn default() -> Self {
+        GameOfLife {
+            history_depth: HISTORY_DEPTH,
+            step_height: STEP_HEIGHT,
+            color_mode: COLOR_MODE,
+        }
+    }
+}
 
-type CellGrid = HashMap<Position, Cell>;
+/// A generation is just the set of positions that are alive; everything not
+/// in the set is dead. This is sparse on purpose: large grids are mostly
+/// empty space, and the step below only ever needs to look at live cells
+/// and their neighbors.
+type CellGrid = HashSet<Position>;
 
 #[derive(Resource, Default)]
 struct Grid {
-    cells: CellGrid,
+    live: CellGrid,
+    /// Consecutive generations each live cell has survived, reset to 0 on
+    /// death and incremented on survival. Only meaningful for positions
+    /// currently in `live`.
+    ages: HashMap<Position, u32>,
+    /// Live-neighbor count each live cell was evaluated with this tick, kept
+    /// around for `ColorMode::Neighbors`.
+    neighbor_counts: HashMap<Position, i32>,
+}
+
+#[derive(Resource, Clone, Copy)]
+struct TimeTowerConfig {
+    history_depth: usize,
+    step_height: f32,
+}
+
+/// The entity that the initial grid (and every time-tower layer) is
+/// parented to, so generations stay aligned with each other.
+#[derive(Resource)]
+struct GridRoot(Entity);
+
+/// Tags a cube entity with the generation it belongs to in time-tower mode.
+#[derive(Component)]
+struct GenerationLayer(usize);
+
+/// Ring buffer of the last `history_depth` generations, kept so that the
+/// oldest layer can be despawned once the tower reaches its configured
+/// depth.
+#[derive(Resource, Default)]
+struct History {
+    generations: VecDeque<CellGrid>,
+    entities: VecDeque<Vec<Entity>>,
+    next_index: usize,
+}
+
+/// Marker type scoping `bevy_mod_raycast`'s source/mesh pairing to cell
+/// picking, so it doesn't interfere with raycasting added by other features.
+struct PickingRaycastSet;
+
+/// When paused, the fixed-timestep step is skipped so a seed pattern can be
+/// hand-authored with the mouse and advanced one generation at a time.
+#[derive(Resource, Default)]
+struct SimulationState {
+    paused: bool,
 }
 
 impl Grid {
     fn new(width: i32, height: i32, depth: i32) -> Grid {
-        let mut cells = CellGrid::new();
+        let mut live = CellGrid::new();
+        let mut ages = HashMap::new();
         for x in 0..width {
             for y in 0..height {
                 for z in 0..depth {
-                    let state = if rand::random() {
-                        CellState::Alive
-                    } else {
-                        CellState::Dead
-                    };
-                    let position = Position::new(x, y, z);
-                    cells.insert(position, Cell::new(state));
+                    if rand::random() {
+                        let position = Position::new(x, y, z);
+                        ages.insert(position.clone(), 0);
+                        live.insert(position);
+                    }
                 }
             }
         }
-        Grid { cells }
-    }
-
-    fn get_cell_mut(&mut self, position: &Position) -> Option<&mut Cell> {
-        self.cells.get_mut(position)
+        Grid {
+            live,
+            ages,
+            neighbor_counts: HashMap::new(),
+        }
     }
 
-    fn get_cell(&self, position: &Position) -> Option<&Cell> {
-        self.cells.get(position)
+    fn is_alive(&self, position: &Position) -> bool {
+        self.live.contains(position)
     }
 }
 impl Plugin for GameOfLife {
     fn build(&self, app: &mut App) {
         app.insert_resource(ClearColor(Color::BLACK))
             .insert_resource(Grid::new(GRID_WIDTH, GRID_HEIGHT, GRID_DEPTH))
+            .insert_resource(TimeTowerConfig {
+                history_depth: self.history_depth,
+                step_height: self.step_height,
+            })
+            .insert_resource(History::default())
+            .insert_resource(SimulationState::default())
+            .insert_resource(Rules::default())
+            .insert_resource(ColorConfig {
+                mode: self.color_mode,
+            })
             .add_startup_system(setup_game)
+            .add_system(
+                update_raycast_with_cursor
+                    .before(RaycastSystem::BuildRays::<PickingRaycastSet>),
+            )
+            .add_system(paint_cell_system)
+            .add_system(pause_input_system)
+            .add_system(step_input_system)
+            .add_system(save_load_input_system)
+            .add_system(pattern_io_input_system)
+            .add_system(rules_ui_system)
             .add_system_set(
                 SystemSet::new()
                     .with_run_criteria(FixedTimestep::step(TIME_STEP))
-                    .with_system(print_position_system),
+                    .with_system(print_position_system.label("step"))
+                    .with_system(time_tower_system.after("step"))
+                    .with_system(color_cells_system.after("step")),
             );
     }
 }
 
-fn live_neighbors(grid: &Grid, position: &Position) -> i32 {
+fn live_neighbors(live: &HashSet<Position>, position: &Position) -> i32 {
     let mut alives = 0;
     for x in -1..=1 {
         for y in -1..=1 {
@@ -120,8 +287,8 @@ fn live_neighbors(grid: &Grid, position: &Position) -> i32 {
                 }
 
                 let key = Position::new(position.x + x, position.y + y, position.z + z);
-                if let Some(cell) = grid.get_cell(&key) {
-                    alives += if cell.is_alive() { 1 } else { 0 }
+                if live.contains(&key) {
+                    alives += 1;
                 }
             }
         }
@@ -129,29 +296,411 @@ fn live_neighbors(grid: &Grid, position: &Position) -> i32 {
     alives
 }
 
-fn print_position_system(mut grid: ResMut<Grid>, mut query: Query<(&Position, &mut Visibility)>) {
+/// Computes the next generation synchronously: every cell's next state is
+/// decided purely from the current (pre-tick) snapshot, then the grid is
+/// swapped in one go, so a cell updated earlier in the tick can never leak
+/// into its neighbors' evaluation. Only live cells and their 26 neighbors
+/// are considered, since everything else is guaranteed to stay dead.
+fn step_generation(grid: &mut Grid, rules: &Rules) {
+    let previous = &grid.live;
+
+    let mut candidates: HashSet<Position> = HashSet::new();
+    for position in previous.iter() {
+        candidates.insert(position.clone());
+        for x in -1..=1 {
+            for y in -1..=1 {
+                for z in -1..=1 {
+                    if x == 0 && y == 0 && z == 0 {
+                        continue;
+                    }
+                    candidates.insert(Position::new(position.x + x, position.y + y, position.z + z));
+                }
+            }
+        }
+    }
+
+    let mut next = CellGrid::new();
+    let mut next_ages: HashMap<Position, u32> = HashMap::new();
+    let mut next_counts: HashMap<Position, i32> = HashMap::new();
+    for position in candidates.into_iter() {
+        let alive = live_neighbors(previous, &position);
+        let survives = previous.contains(&position) && rules.survival.contains(&alive);
+        let born = !previous.contains(&position) && rules.birth.contains(&alive);
+        if survives || born {
+            let age = if survives {
+                grid.ages.get(&position).copied().unwrap_or(0) + 1
+            } else {
+                0
+            };
+            next_ages.insert(position.clone(), age);
+            next_counts.insert(position.clone(), alive);
+            next.insert(position);
+        }
+    }
+
+    grid.live = next;
+    grid.ages = next_ages;
+    grid.neighbor_counts = next_counts;
+}
+
+fn sync_visibility(grid: &Grid, query: &mut Query<(&Position, &mut Visibility)>) {
     for (position, mut visibility) in query.iter_mut() {
-        let alive = live_neighbors(&grid, position);
-        let cell = grid.get_cell_mut(position).unwrap();
+        visibility.is_visible = grid.is_alive(position);
+    }
+}
 
-        match cell.state {
-            CellState::Alive => {
-                if EB <= alive && alive <= EH {
-                    cell.state = CellState::Alive;
-                } else {
-                    cell.state = CellState::Dead;
+fn print_position_system(
+    state: Res<SimulationState>,
+    rules: Res<Rules>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    if state.paused {
+        return;
+    }
+
+    step_generation(&mut grid, &rules);
+    sync_visibility(&grid, &mut query);
+}
+
+/// Recolors each live cube based on the configured `ColorMode`, so stable
+/// cores (long-lived, densely surrounded cells) read differently from the
+/// flickering edges of the automaton. A no-op in `ColorMode::Static`.
+fn color_cells_system(
+    grid: Res<Grid>,
+    config: Res<ColorConfig>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    query: Query<(&Position, &Handle<StandardMaterial>)>,
+) {
+    if config.mode == ColorMode::Static {
+        return;
+    }
+
+    for (position, material_handle) in query.iter() {
+        if !grid.live.contains(position) {
+            continue;
+        }
+
+        let t = match config.mode {
+            ColorMode::Age => {
+                let age = grid.ages.get(position).copied().unwrap_or(0);
+                (age as f32 / AGE_COLOR_SCALE).min(1.0)
+            }
+            ColorMode::Neighbors => {
+                let neighbors = grid.neighbor_counts.get(position).copied().unwrap_or(0);
+                (neighbors as f32 / 26.0).min(1.0)
+            }
+            ColorMode::Static => unreachable!(),
+        };
+
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.base_color = Color::rgba(0.02 + t * 0.9, 0.8 - t * 0.6, 0.08 + t * 0.1, 1.0);
+        }
+    }
+}
+
+/// Toggles the simulation's paused state, so a seed pattern can be painted
+/// by hand without it changing out from under the cursor.
+fn pause_input_system(keyboard: Res<Input<KeyCode>>, mut state: ResMut<SimulationState>) {
+    if keyboard.just_pressed(KeyCode::Space) {
+        state.paused = !state.paused;
+    }
+}
+
+/// Advances exactly one generation on demand, for inspecting the automaton
+/// step by step while paused.
+fn step_input_system(
+    keyboard: Res<Input<KeyCode>>,
+    state: Res<SimulationState>,
+    rules: Res<Rules>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    if !state.paused || !keyboard.just_pressed(KeyCode::Right) {
+        return;
+    }
+
+    step_generation(&mut grid, &rules);
+    sync_visibility(&grid, &mut query);
+}
+
+/// egui overlay exposing the four Bays thresholds as live sliders, a dropdown
+/// of named presets, and a reseed button, so the rule space can be explored
+/// without recompiling.
+fn rules_ui_system(
+    mut egui_ctx: ResMut<EguiContext>,
+    mut rules: ResMut<Rules>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    let presets = rule_presets();
+    let current_label = presets
+        .iter()
+        .find(|preset| preset.survival == rules.survival && preset.birth == rules.birth)
+        .map(|preset| preset.name)
+        .unwrap_or("Custom");
+
+    egui::Window::new("Rules").show(egui_ctx.ctx_mut(), |ui| {
+        egui::ComboBox::from_label("Preset")
+            .selected_text(current_label)
+            .show_ui(ui, |ui| {
+                for preset in &presets {
+                    if ui
+                        .selectable_label(current_label == preset.name, preset.name)
+                        .clicked()
+                    {
+                        rules.survival = preset.survival.clone();
+                        rules.birth = preset.birth.clone();
+                    }
+                }
+            });
+
+        let mut survival_start = *rules.survival.start();
+        let mut survival_end = *rules.survival.end();
+        let mut birth_start = *rules.birth.start();
+        let mut birth_end = *rules.birth.end();
+
+        ui.add(egui::Slider::new(&mut survival_start, 0..=26).text("EB (survive from)"));
+        ui.add(egui::Slider::new(&mut survival_end, 0..=26).text("EH (survive to)"));
+        ui.add(egui::Slider::new(&mut birth_start, 0..=26).text("FB (birth from)"));
+        ui.add(egui::Slider::new(&mut birth_end, 0..=26).text("FH (birth to)"));
+
+        rules.survival = survival_start..=survival_end;
+        rules.birth = birth_start..=birth_end;
+
+        if ui.button("Reseed").clicked() {
+            *grid = Grid::new(GRID_WIDTH, GRID_HEIGHT, GRID_DEPTH);
+            sync_visibility(&grid, &mut query);
+        }
+    });
+}
+
+/// Ctrl+S saves the current live set to `SAVE_FILE`; Ctrl+L loads it back,
+/// replacing the running grid. Both use RON so the file stays human-editable.
+fn save_load_input_system(
+    keyboard: Res<Input<KeyCode>>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    let ctrl = keyboard.pressed(KeyCode::LControl) || keyboard.pressed(KeyCode::RControl);
+    if !ctrl {
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::S) {
+        let pattern = Pattern {
+            cells: grid.live.iter().cloned().collect(),
+        };
+        match ron::ser::to_string_pretty(&pattern, ron::ser::PrettyConfig::default()) {
+            Ok(contents) => {
+                if let Err(error) = std::fs::write(SAVE_FILE, contents) {
+                    eprintln!("failed to save grid to {}: {}", SAVE_FILE, error);
+                }
+            }
+            Err(error) => eprintln!("failed to serialize grid: {}", error),
+        }
+    }
+
+    if keyboard.just_pressed(KeyCode::L) {
+        match std::fs::read_to_string(SAVE_FILE) {
+            Ok(contents) => match ron::de::from_str::<Pattern>(&contents) {
+                Ok(pattern) => {
+                    grid.live = pattern.cells.into_iter().collect();
+                    grid.ages = grid.live.iter().map(|position| (position.clone(), 0)).collect();
+                    grid.neighbor_counts.clear();
+                    sync_visibility(&grid, &mut query);
                 }
+                Err(error) => eprintln!("failed to parse {}: {}", SAVE_FILE, error),
+            },
+            Err(error) => eprintln!("failed to read {}: {}", SAVE_FILE, error),
+        }
+    }
+}
+
+/// Ctrl+E exports the live set as a standalone pattern, with coordinates
+/// made relative to its bounding box's minimum corner, so known 3D
+/// oscillators/gliders can be shared. Ctrl+I imports one back, translated by
+/// `IMPORT_OFFSET`.
+fn pattern_io_input_system(
+    keyboard: Res<Input<KeyCode>>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    let ctrl = keyboard.pressed(KeyCode::LControl) || keyboard.pressed(KeyCode::RControl);
+    if !ctrl {
+        return;
+    }
+
+    if keyboard.just_pressed(KeyCode::E) {
+        export_pattern(&grid);
+    }
+
+    if keyboard.just_pressed(KeyCode::I) {
+        import_pattern(&mut grid, &mut query);
+    }
+}
+
+fn export_pattern(grid: &Grid) {
+    let min_x = grid.live.iter().map(|position| position.x).min().unwrap_or(0);
+    let min_y = grid.live.iter().map(|position| position.y).min().unwrap_or(0);
+    let min_z = grid.live.iter().map(|position| position.z).min().unwrap_or(0);
+
+    let pattern = Pattern {
+        cells: grid
+            .live
+            .iter()
+            .map(|position| {
+                Position::new(position.x - min_x, position.y - min_y, position.z - min_z)
+            })
+            .collect(),
+    };
+
+    match ron::ser::to_string_pretty(&pattern, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => {
+            if let Err(error) = std::fs::write(PATTERN_FILE, contents) {
+                eprintln!("failed to export pattern to {}: {}", PATTERN_FILE, error);
             }
-            CellState::Dead => {
-                if FB <= alive && alive <= FH {
-                    cell.state = CellState::Alive;
+        }
+        Err(error) => eprintln!("failed to serialize pattern: {}", error),
+    }
+}
+
+fn import_pattern(grid: &mut Grid, query: &mut Query<(&Position, &mut Visibility)>) {
+    let contents = match std::fs::read_to_string(PATTERN_FILE) {
+        Ok(contents) => contents,
+        Err(error) => {
+            eprintln!("failed to read {}: {}", PATTERN_FILE, error);
+            return;
+        }
+    };
+
+    let pattern: Pattern = match ron::de::from_str(&contents) {
+        Ok(pattern) => pattern,
+        Err(error) => {
+            eprintln!("failed to parse {}: {}", PATTERN_FILE, error);
+            return;
+        }
+    };
+
+    for position in pattern.cells {
+        grid.live.insert(Position::new(
+            position.x + IMPORT_OFFSET.x,
+            position.y + IMPORT_OFFSET.y,
+            position.z + IMPORT_OFFSET.z,
+        ));
+    }
+
+    sync_visibility(grid, query);
+}
+
+/// Keeps the picking ray's source glued to the cursor so clicks are raycast
+/// against whatever cube is currently under the mouse.
+fn update_raycast_with_cursor(
+    mut cursor: EventReader<CursorMoved>,
+    mut sources: Query<&mut RayCastSource<PickingRaycastSet>>,
+) {
+    if let Some(cursor_moved) = cursor.iter().last() {
+        for mut source in sources.iter_mut() {
+            source.cast_method = RayCastMethod::Screenspace(cursor_moved.position);
+        }
+    }
+}
+
+/// Left-click toggles whichever cube is under the cursor alive/dead, so a
+/// seed pattern can be hand-authored instead of always starting from
+/// `rand::random()` noise.
+fn paint_cell_system(
+    mouse: Res<Input<MouseButton>>,
+    sources: Query<&RayCastSource<PickingRaycastSet>>,
+    mut grid: ResMut<Grid>,
+    mut query: Query<(&Position, &mut Visibility)>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    for source in sources.iter() {
+        if let Some((entity, _)) = source.intersect_top() {
+            if let Ok((position, mut visibility)) = query.get_mut(entity) {
+                if grid.live.contains(position) {
+                    grid.live.remove(position);
+                    grid.ages.remove(position);
                 } else {
-                    cell.state = CellState::Dead;
+                    grid.live.insert(position.clone());
+                    grid.ages.insert(position.clone(), 0);
                 }
+                visibility.is_visible = grid.is_alive(position);
+            }
+        }
+    }
+}
+
+/// Stacks a snapshot of the current generation above the previous ones when
+/// time-tower mode is enabled, fading older layers out and discarding them
+/// once the configured history depth is exceeded.
+fn time_tower_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    state: Res<SimulationState>,
+    grid: Res<Grid>,
+    grid_root: Res<GridRoot>,
+    config: Res<TimeTowerConfig>,
+    mut history: ResMut<History>,
+    mut layers: Query<(&GenerationLayer, &Handle<StandardMaterial>)>,
+) {
+    if config.history_depth == 0 || state.paused {
+        return;
+    }
+
+    let generation_index = history.next_index;
+    history.next_index += 1;
+
+    let entities: Vec<Entity> = grid
+        .live
+        .iter()
+        .map(|position| {
+            commands
+                .spawn((
+                    GenerationLayer(generation_index),
+                    PbrBundle {
+                        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+                        material: materials.add(StandardMaterial {
+                            base_color: Color::rgba(0.02, 0.8, 0.08, 1.0),
+                            alpha_mode: AlphaMode::Blend,
+                            ..StandardMaterial::default()
+                        }),
+                        transform: Transform::from_xyz(
+                            position.x as f32 + 5.0,
+                            position.y as f32 + 5.0 + generation_index as f32 * config.step_height,
+                            position.z as f32 + 5.0,
+                        ),
+                        ..PbrBundle::default()
+                    },
+                ))
+                .id()
+        })
+        .collect();
+    commands.entity(grid_root.0).push_children(&entities);
+
+    history.generations.push_back(grid.live.clone());
+    history.entities.push_back(entities);
+
+    if history.generations.len() > config.history_depth {
+        history.generations.pop_front();
+        if let Some(oldest) = history.entities.pop_front() {
+            for entity in oldest {
+                commands.entity(entity).despawn();
             }
         }
+    }
 
-        visibility.is_visible = cell.is_alive();
+    for (layer, material_handle) in layers.iter_mut() {
+        let age = generation_index.saturating_sub(layer.0);
+        let alpha = (1.0 - age as f32 / config.history_depth as f32).max(0.08);
+        if let Some(material) = materials.get_mut(material_handle) {
+            material.base_color.set_a(alpha);
+        }
     }
 }
 
@@ -174,7 +723,8 @@ fn setup_game(
                 GRID_DEPTH as f32 * 2.0,
             ),
             Vec3::ZERO,
-        ));
+        ))
+        .insert(RayCastSource::<PickingRaycastSet>::new());
 
     for x in 0..=2 {
         for y in 0..=2 {
@@ -211,32 +761,43 @@ fn setup_game(
         })
         .id();
 
-    let children: Vec<Entity> = grid
-        .cells
-        .iter()
-        .map(|(position, cell)| {
-            commands
-                .spawn((
-                    position.clone(),
-                    PbrBundle {
-                        visibility: Visibility {
-                            is_visible: cell.state == CellState::Alive,
+    let mut children: Vec<Entity> = Vec::new();
+    for x in 0..GRID_WIDTH {
+        for y in 0..GRID_HEIGHT {
+            for z in 0..GRID_DEPTH {
+                let position = Position::new(x, y, z);
+                let is_alive = grid.is_alive(&position);
+                let entity = commands
+                    .spawn((
+                        position,
+                        GenerationLayer(0),
+                        RayCastMesh::<PickingRaycastSet>::default(),
+                        PbrBundle {
+                            visibility: Visibility {
+                                is_visible: is_alive,
+                            },
+                            mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
+                            material: materials.add(StandardMaterial {
+                                base_color: Color::rgba(0.02, 0.8, 0.08, 1.0),
+                                alpha_mode: AlphaMode::Blend,
+                                ..StandardMaterial::default()
+                            }),
+                            transform: Transform::from_xyz(
+                                x as f32 + 5.0,
+                                y as f32 + 5.0,
+                                z as f32 + 5.0,
+                            ),
+                            ..PbrBundle::default()
                         },
-                        mesh: meshes.add(Mesh::from(shape::Cube { size: 1.0 })),
-                        material: materials.add(Color::rgb(0.02, 0.8, 0.08).into()),
-                        transform: Transform::from_xyz(
-                            position.x as f32 + 5.0,
-                            position.y as f32 + 5.0,
-                            position.z as f32 + 5.0,
-                        ),
-                        ..PbrBundle::default()
-                    },
-                ))
-                .id()
-        })
-        .collect();
+                    ))
+                    .id();
+                children.push(entity);
+            }
+        }
+    }
 
     commands.entity(parent).push_children(&children);
+    commands.insert_resource(GridRoot(parent));
 }
 
 fn main() {
@@ -245,6 +806,12 @@ fn main() {
         .add_plugins(DefaultPlugins)
         .add_plugin(LookTransformPlugin)
         .add_plugin(OrbitCameraPlugin::default())
-        .add_plugin(GameOfLife)
+        .add_plugin(DefaultRaycastingPlugin::<PickingRaycastSet>::default())
+        .add_plugin(EguiPlugin)
+        .add_plugin(GameOfLife {
+            history_depth: HISTORY_DEPTH,
+            step_height: STEP_HEIGHT,
+            color_mode: COLOR_MODE,
+        })
         .run();
 }